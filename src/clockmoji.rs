@@ -1,5 +1,8 @@
 use std::ops::Add;
-use chrono::{DateTime, Duration, Timelike, Local};
+use chrono::{DateTime, Duration, Timelike, Local, Utc};
+#[cfg(test)]
+use chrono::offset::TimeZone;
+use chrono_tz::Tz;
 
 const CLOCKS: [&str; 24] = [
     "🕛", "🕧", "🕐", "🕜", "🕑", "🕝", "🕒", "🕞", "🕓", "🕟", "🕔", "🕠",
@@ -16,6 +19,35 @@ pub fn get_emoji(time: &Option<DateTime<Local>>) -> &'static str {
     CLOCKS[index % CLOCKS.len()]
 }
 
+/// Like `get_emoji`, but renders the clock face for an arbitrary IANA zone
+/// instead of the machine's `Local`, correctly honoring that zone's DST
+/// transitions. Takes a resolved `Tz` rather than an IANA name so that an
+/// unrecognized zone is a parse error the caller sees (via `str::parse`),
+/// not a silent fallback to UTC.
+pub fn get_clock_emoji_tz(time: DateTime<Utc>, tz: Tz) -> &'static str {
+    let time = time.with_timezone(&tz);
+    let time = time.add(Duration::minutes(15));
+    let seconds = time.time().num_seconds_from_midnight() as usize;
+    let index = seconds / DURATION;
+
+    CLOCKS[index % CLOCKS.len()]
+}
+
 fn main() {
     println!("{}", get_emoji(&None));
 }
+
+#[test]
+fn a() {
+    // 2024-06-20T13:00:00Z is 09:00 EDT in New York (UTC-4 under DST).
+    let test_time = Utc.ymd(2024, 6, 20).and_hms(13, 0, 0);
+    let tz: Tz = "America/New_York".parse().unwrap();
+    assert_eq!(get_clock_emoji_tz(test_time, tz), "🕘");
+
+    // 2024-01-20T14:00:00Z is 09:00 EST in New York (UTC-5, no DST) -
+    // same local hour as above via a different UTC offset.
+    let winter_time = Utc.ymd(2024, 1, 20).and_hms(14, 0, 0);
+    assert_eq!(get_clock_emoji_tz(winter_time, tz), "🕘");
+
+    assert!("Europe/Londn".parse::<Tz>().is_err());
+}
@@ -1,9 +1,10 @@
 use std::f64::consts::PI;
 use std::ops::{Add, Sub};
 
-use chrono::{DateTime, Duration, Utc};
-#[cfg(test)]
+use chrono::{DateTime, Datelike, Duration, Utc};
 use chrono::offset::TimeZone;
+#[cfg(test)]
+use chrono::Timelike;
 use rand::random;
 
 const DAY_MILLIS: f64 = 1000.0 * 60.0 * 60.0 * 24.0;
@@ -50,6 +51,18 @@ pub struct MoonCoords {
     dist: f64,
 }
 
+/// An observer's position on Earth, in degrees/metres.
+#[derive(Debug, Clone, Copy)]
+pub struct Location {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub elevation: f64,
+}
+
+const MOON_HORIZON: f64 = RADS * -0.833;
+const BELOW_HORIZON: &str = "🌌";
+const EARTH_RADIUS_KM: f64 = 6378.14;
+
 fn to_days(date: &DateTime<Utc>) -> f64 {
    date.timestamp_millis() as f64 / DAY_MILLIS - 0.5 + J1970 - J2000
 }
@@ -87,10 +100,215 @@ fn moon_coords(d: f64) -> MoonCoords {
     }
 }
 
-fn get_phase(date: &Option<DateTime<Utc>>) -> f64 {
+/// One of the four principal lunar phases.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    New,
+    FirstQuarter,
+    Full,
+    LastQuarter,
+}
+
+impl Phase {
+    fn offset(self) -> f64 {
+        match self {
+            Phase::New => 0.0,
+            Phase::FirstQuarter => 0.25,
+            Phase::Full => 0.5,
+            Phase::LastQuarter => 0.75,
+        }
+    }
+}
+
+fn year_fraction(date: &DateTime<Utc>) -> f64 {
+    let year = date.year();
+    let start = Utc.ymd(year, 1, 1).and_hms(0, 0, 0);
+    let end = Utc.ymd(year + 1, 1, 1).and_hms(0, 0, 0);
+    let elapsed = (*date - start).num_milliseconds() as f64;
+    let total = (end - start).num_milliseconds() as f64;
+
+    year as f64 + elapsed / total
+}
+
+// Meeus' lunation method (Astronomical Algorithms, ch. 49): mean phase JDE
+// plus the dominant periodic correction terms for the given principal phase.
+fn phase_jde(k: f64, target: Phase) -> f64 {
+    let t = k / 1236.85;
+
+    let jde = 2_451_550.097_66 + 29.530_588_861 * k
+        + 0.000_154_37 * t.powi(2)
+        - 0.000_000_150 * t.powi(3)
+        + 0.000_000_000_73 * t.powi(4);
+
+    let m = RADS * (2.5534 + 29.105_356_70 * k);
+    let m_prime = RADS * (201.5643 + 385.816_935_28 * k);
+    let f = RADS * (160.7108 + 390.670_502_84 * k);
+    let omega = RADS * (124.7746 - 1.563_755 * k);
+    let e = 1.0 - 0.002_516 * t - 0.000_0074 * t.powi(2);
+
+    let correction = if target == Phase::New || target == Phase::Full {
+        -0.407_20 * m_prime.sin()
+            + 0.172_41 * e * m.sin()
+            + 0.016_08 * (2.0 * m_prime).sin()
+            + 0.010_39 * (2.0 * f).sin()
+            + 0.007_39 * e * (m_prime - m).sin()
+            - 0.005_14 * e * (m_prime + m).sin()
+            + 0.002_08 * e * e * (2.0 * m).sin()
+            - 0.001_11 * (m_prime - 2.0 * f).sin()
+            - 0.000_57 * (m_prime + 2.0 * f).sin()
+            + 0.000_56 * e * (2.0 * m_prime + m).sin()
+            - 0.000_42 * (3.0 * m_prime).sin()
+            + 0.000_42 * e * (m + 2.0 * f).sin()
+            + 0.000_38 * e * (m - 2.0 * f).sin()
+            - 0.000_24 * e * (2.0 * m_prime - m).sin()
+            - 0.000_17 * omega.sin()
+    } else {
+        let w = 0.003_06 - 0.000_38 * e * m.cos() + 0.000_26 * m_prime.cos();
+        let w = if target == Phase::FirstQuarter { w } else { -w };
+
+        -0.628_01 * m_prime.sin()
+            + 0.171_72 * e * m.sin()
+            - 0.011_83 * e * (m_prime + m).sin()
+            + 0.008_62 * (2.0 * m_prime).sin()
+            + 0.008_04 * (2.0 * f).sin()
+            + 0.004_54 * e * (m_prime - m).sin()
+            + 0.002_04 * e * e * (2.0 * m).sin()
+            - 0.001_80 * (m_prime - 2.0 * f).sin()
+            - 0.000_70 * (m_prime + 2.0 * f).sin()
+            - 0.000_40 * (3.0 * m_prime).sin()
+            - 0.000_17 * omega.sin()
+            + w
+    };
+
+    jde + correction
+}
+
+fn jde_to_date(jde: f64) -> DateTime<Utc> {
+    let millis = (jde - J1970 + 0.5) * DAY_MILLIS;
+
+    Utc.timestamp_millis(millis.round() as i64)
+}
+
+/// Returns the instant of the next occurrence of `target` after `after`,
+/// per Meeus' lunation method.
+pub fn next_phase(after: DateTime<Utc>, target: Phase) -> DateTime<Utc> {
+    let mut k = ((year_fraction(&after) - 2000.0) * 12.3685).floor() + target.offset();
+
+    loop {
+        let candidate = jde_to_date(phase_jde(k, target));
+        if candidate > after {
+            return candidate;
+        }
+        k += 1.0;
+    }
+}
+
+fn sidereal_time(d: f64, lw: f64) -> f64 {
+    RADS * (280.16 + 360.985_623_5 * d) - lw
+}
+
+fn altitude(h: f64, phi: f64, dec: f64) -> f64 {
+    (phi.sin() * dec.sin() + phi.cos() * dec.cos() * h.cos()).asin()
+}
+
+// Corrects the geocentric moon position for the observer's location on
+// Earth's surface (Meeus ch. 40, parallax-in-right-ascension / -declination).
+// `location.elevation` (metres) moves the observer further from Earth's
+// centre, which is folded into the horizontal parallax used for all three
+// corrected coordinates, including a first-order correction to `dist`
+// (the line-of-sight component of the observer's displacement).
+fn topocentric_moon(d: f64, location: &Location) -> MoonCoords {
+    let geocentric = moon_coords(d);
+    let lat = RADS * location.latitude;
+    let lw = RADS * -location.longitude;
+    let h = sidereal_time(d, lw) - geocentric.ra;
+    let rho_km = EARTH_RADIUS_KM + location.elevation / 1000.0;
+    let parallax = (rho_km / geocentric.dist).asin();
+
+    let delta_ra = (-parallax.sin() * lat.cos() * h.sin())
+        .atan2(geocentric.dec.cos() - parallax.sin() * lat.cos() * h.cos());
+    let dec = ((geocentric.dec.sin() - parallax.sin() * lat.sin()) * delta_ra.cos())
+        .atan2(geocentric.dec.cos() - parallax.sin() * lat.cos() * h.cos());
+    let geocentric_alt = altitude(h, lat, geocentric.dec);
+    let dist = geocentric.dist - rho_km * geocentric_alt.sin();
+
+    MoonCoords {
+        ra: geocentric.ra + delta_ra,
+        dec,
+        dist,
+    }
+}
+
+fn moon_altitude(d: f64, location: &Location) -> f64 {
+    let topo = topocentric_moon(d, location);
+    let lat = RADS * location.latitude;
+    let lw = RADS * -location.longitude;
+    let h = sidereal_time(d, lw) - topo.ra;
+
+    altitude(h, lat, topo.dec)
+}
+
+fn scan_horizon_crossing(date: &DateTime<Utc>, location: &Location, rising: bool) -> Option<DateTime<Utc>> {
+    let midnight = date.date().and_hms(0, 0, 0);
+    let step = Duration::minutes(10);
+    let samples = 24 * 6;
+
+    let mut prev = to_days(&midnight);
+    let mut prev_alt = moon_altitude(prev, location) - MOON_HORIZON;
+
+    for i in 1..=samples {
+        let t = midnight.add(step * i as i32);
+        let d = to_days(&t);
+        let alt = moon_altitude(d, location) - MOON_HORIZON;
+
+        let crossed_up = prev_alt < 0.0 && alt >= 0.0;
+        let crossed_down = prev_alt >= 0.0 && alt < 0.0;
+
+        if (rising && crossed_up) || (!rising && crossed_down) {
+            let fraction = prev_alt / (prev_alt - alt);
+            let d_cross = prev + (d - prev) * fraction;
+            let millis = (d_cross + J2000 - J1970 + 0.5) * DAY_MILLIS;
+            return Some(Utc.timestamp_millis(millis.round() as i64));
+        }
+
+        prev = d;
+        prev_alt = alt;
+    }
+
+    None
+}
+
+/// The moon's local altitude crosses the standard horizon (-0.833°, rising)
+/// at this instant, or `None` if the moon doesn't rise on this date.
+pub fn moonrise(date: &DateTime<Utc>, location: &Location) -> Option<DateTime<Utc>> {
+    scan_horizon_crossing(date, location, true)
+}
+
+/// The moon's local altitude crosses the standard horizon (-0.833°, setting)
+/// at this instant, or `None` if the moon doesn't set on this date.
+pub fn moonset(date: &DateTime<Utc>, location: &Location) -> Option<DateTime<Utc>> {
+    scan_horizon_crossing(date, location, false)
+}
+
+/// Like `get_emoji`, but accounts for the observer's location: if the moon
+/// is below the local horizon, returns `BELOW_HORIZON` instead of the phase
+/// emoji.
+pub fn get_emoji_at(date: &Option<DateTime<Utc>>, location: &Location) -> &'static str {
     let date = date.unwrap_or_else(Utc::now);
     let d = to_days(&date);
 
+    if moon_altitude(d, location) < MOON_HORIZON {
+        return BELOW_HORIZON;
+    }
+
+    get_emoji(&Some(date))
+}
+
+const SYNODIC_MONTH_DAYS: f64 = 29.530_588_861;
+
+// Phase angle `inc` and the signed waxing/waning `angle`, shared by
+// `get_phase` and `get_phase_info`.
+fn phase_angles(d: f64) -> (f64, f64) {
     let s = sun_coords(d);
     let m = moon_coords(d);
 
@@ -99,9 +317,45 @@ fn get_phase(date: &Option<DateTime<Utc>>) -> f64 {
     let angle = (s.dec.cos() * (s.ra - m.ra).sin()).atan2(
         s.dec.sin() * m.dec.cos() - s.dec.cos() * m.dec.sin() * (s.ra - m.ra).cos()
     );
+
+    (inc, angle)
+}
+
+fn get_phase(date: &Option<DateTime<Utc>>) -> f64 {
+    let date = date.unwrap_or_else(Utc::now);
+    let d = to_days(&date);
+
+    let (inc, angle) = phase_angles(d);
     0.5 + 0.5 * inc * ONE.copysign(angle) / PI
 }
 
+/// The numeric data behind a moon phase, for callers that want more than
+/// just the emoji (e.g. a progress bar or "87% illuminated" label).
+#[derive(Debug)]
+pub struct PhaseInfo {
+    pub fraction_illuminated: f64,
+    pub phase_angle: f64,
+    pub waxing: bool,
+    pub age_days: f64,
+    pub emoji: &'static str,
+}
+
+pub fn get_phase_info(date: &Option<DateTime<Utc>>) -> PhaseInfo {
+    let date = date.unwrap_or_else(Utc::now);
+    let d = to_days(&date);
+
+    let (inc, angle) = phase_angles(d);
+    let phase = 0.5 + 0.5 * inc * ONE.copysign(angle) / PI;
+
+    PhaseInfo {
+        fraction_illuminated: (1.0 + inc.cos()) / 2.0,
+        phase_angle: inc,
+        waxing: angle < 0.0,
+        age_days: phase * SYNODIC_MONTH_DAYS,
+        emoji: PHASES[step_phase(phase, None)].emoji,
+    }
+}
+
 fn step_phase(phase: f64, random_value: Option<f64>) -> usize {
     let extra_emoji = random::<f64>() <= random_value.unwrap_or(0.1);
     let mut phase = phase * PHASE_WEIGHT;
@@ -165,3 +419,56 @@ fn a() {
 
     assert_eq!(step_phase(0.0, None), 0);
 }
+
+#[test]
+fn b() {
+    let after = Utc.ymd(2000, 1, 1).and_hms(0, 0, 0);
+    let new_moon = next_phase(after, Phase::New);
+    assert_eq!(new_moon, Utc.ymd(2000, 1, 6).and_hms(18, 15, 22) + Duration::milliseconds(818));
+
+    // The next full moon after a new moon is a little under half a synodic
+    // month later.
+    let full_moon = next_phase(new_moon, Phase::Full);
+    assert!(full_moon > new_moon);
+    assert!((full_moon - new_moon).num_days() >= 13 && (full_moon - new_moon).num_days() <= 15);
+}
+
+#[test]
+fn c() {
+    let kyiv = Location { latitude: 50.5, longitude: 30.5, elevation: 0.0 };
+    let test_date = Utc.ymd(2013, 3, 1).and_hms(0, 0, 0);
+
+    let rise = moonrise(&test_date, &kyiv).unwrap();
+    assert_eq!(rise.time().hour(), 20);
+    assert!((rise.time().minute() as i64 - 18).abs() <= 1);
+
+    let set = moonset(&test_date, &kyiv).unwrap();
+    assert_eq!(set.time().hour(), 5);
+    assert!((set.time().minute() as i64 - 48).abs() <= 1);
+
+    // Raising the observer moves them further from Earth's centre, which
+    // should shorten the observer-to-moon distance.
+    let test_days = to_days(&test_date);
+    let sea_level = topocentric_moon(test_days, &kyiv);
+    let everest = Location { elevation: 8_848.0, ..kyiv };
+    let on_everest = topocentric_moon(test_days, &everest);
+    assert!(on_everest.dist < sea_level.dist);
+}
+
+#[test]
+fn d() {
+    // 2013-03-14 sits in the waxing crescent, well before Full Moon.
+    let waxing_date = Utc.ymd(2013, 3, 14).and_hms(0, 0, 0);
+    let waxing = get_phase_info(&Some(waxing_date));
+    assert!(waxing.waxing);
+    assert_eq!(waxing.emoji, "🌒");
+
+    // 2013-03-05 sits in the waning gibbous/last-quarter stretch, after Full Moon.
+    let waning_date = Utc.ymd(2013, 3, 5).and_hms(0, 0, 0);
+    let waning = get_phase_info(&Some(waning_date));
+    assert!(!waning.waxing);
+    assert_eq!(waning.emoji, "🌗");
+
+    assert!((waning.fraction_illuminated - (1.0 + waning.phase_angle.cos()) / 2.0).abs() <= std::f64::EPSILON);
+    assert!((waning.age_days - 22.290_777_674_207_224).abs() <= 1e-9);
+}
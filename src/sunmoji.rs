@@ -0,0 +1,219 @@
+use std::f64::consts::PI;
+use std::ops::Add;
+
+use chrono::{DateTime, Duration, Timelike, Utc};
+use chrono::offset::TimeZone;
+
+const DAY_MILLIS: f64 = 1000.0 * 60.0 * 60.0 * 24.0;
+const J1970: f64 = 2_440_588.0;
+const J2000: f64 = 2_451_545.0;
+
+const RADS: f64 = PI / 180.0;
+const EARTH: f64 = RADS * 23.4397; // obliquity of the Earth
+const ZERO: f64 = 0.0;
+const J0: f64 = 0.0009;
+
+const SUN_HORIZON: f64 = RADS * -0.833;
+const CIVIL_TWILIGHT: f64 = RADS * -6.0;
+
+const NIGHT: &str = "🌃";
+const SUNRISE: &str = "🌅";
+const DAY: &str = "☀️";
+const SUNSET: &str = "🌇";
+
+/// An observer's position on Earth, in degrees/metres.
+#[derive(Debug, Clone, Copy)]
+pub struct Location {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub elevation: f64,
+}
+
+#[derive(Debug)]
+pub struct SunCoords {
+    dec: f64,
+    ra: f64,
+}
+
+fn to_days(date: &DateTime<Utc>) -> f64 {
+   date.timestamp_millis() as f64 / DAY_MILLIS - 0.5 + J1970 - J2000
+}
+
+fn solar_mean_anomaly(d: f64) -> f64 {
+    RADS * (357.5291 + 0.985_600_28 * d)
+}
+
+fn ecliptic_longitude(m: f64) -> f64 {
+    let c = RADS * (1.9148 * m.sin() + 0.02 * (2.0 * m).sin() + 0.0003 * (3.0 * m).sin()); // equation of center
+    let p = RADS * 102.9372; // perihelion of the Earth
+
+    m + c + p + PI
+}
+
+fn declination(l: f64) -> f64 {
+    (ZERO.sin() * EARTH.cos() + ZERO.cos() * EARTH.sin() * l.sin()).asin()
+}
+
+fn sun_coords(d: f64) -> SunCoords {
+    let m = solar_mean_anomaly(d);
+    let l = ecliptic_longitude(m);
+
+    SunCoords {
+        dec: declination(l),
+        ra: (l.sin() * EARTH.cos() - ZERO.tan() * EARTH.sin()).atan2(l.cos()),
+    }
+}
+
+fn sidereal_time(d: f64, lw: f64) -> f64 {
+    RADS * (280.16 + 360.985_623_5 * d) - lw
+}
+
+fn altitude(h: f64, phi: f64, dec: f64) -> f64 {
+    (phi.sin() * dec.sin() + phi.cos() * dec.cos() * h.cos()).asin()
+}
+
+fn sun_altitude(d: f64, location: &Location) -> f64 {
+    let s = sun_coords(d);
+    let lat = RADS * location.latitude;
+    let lw = RADS * -location.longitude;
+    let h = sidereal_time(d, lw) - s.ra;
+
+    altitude(h, lat, s.dec)
+}
+
+// Hour angle at which the sun crosses `horizon`, or `None` during polar
+// day/night when the sun never reaches that altitude.
+fn hour_angle(horizon: f64, lat: f64, dec: f64) -> Option<f64> {
+    let cos_h = (horizon.sin() - lat.sin() * dec.sin()) / (lat.cos() * dec.cos());
+
+    if cos_h.abs() > 1.0 {
+        None
+    } else {
+        Some(cos_h.acos())
+    }
+}
+
+fn julian_cycle(d: f64, lw: f64) -> f64 {
+    (d - J0 - lw / (2.0 * PI)).round()
+}
+
+fn approx_transit(ht: f64, lw: f64, n: f64) -> f64 {
+    J0 + (ht + lw) / (2.0 * PI) + n
+}
+
+fn solar_transit_j(ds: f64, m: f64, l: f64) -> f64 {
+    J2000 + ds + 0.0053 * m.sin() - 0.0069 * (2.0 * l).sin()
+}
+
+fn jde_to_date(jde: f64) -> DateTime<Utc> {
+    let millis = (jde - J1970 + 0.5) * DAY_MILLIS;
+
+    Utc.timestamp_millis(millis.round() as i64)
+}
+
+// Julian Ephemeris Day of solar noon, and (if the sun reaches `horizon`)
+// sunrise/sunset, all derived from the same mean anomaly/ecliptic longitude
+// so they agree with each other (mirrors suncalc's `getTimes`).
+fn transit_jdes(d: f64, lw: f64, lat: f64, horizon: f64) -> (f64, Option<f64>, Option<f64>) {
+    let n = julian_cycle(d, lw);
+    let ds = approx_transit(0.0, lw, n);
+    let m = solar_mean_anomaly(ds);
+    let l = ecliptic_longitude(m);
+    let dec = declination(l);
+    let j_noon = solar_transit_j(ds, m, l);
+
+    match hour_angle(horizon, lat, dec) {
+        Some(w) => {
+            let j_set = solar_transit_j(approx_transit(w, lw, n), m, l);
+            let j_rise = j_noon - (j_set - j_noon);
+            (j_noon, Some(j_rise), Some(j_set))
+        }
+        None => (j_noon, None, None),
+    }
+}
+
+/// The sun's local altitude crosses the standard horizon (-0.833°, rising)
+/// at this instant, or `None` during polar day/night.
+pub fn sunrise(date: &DateTime<Utc>, location: &Location) -> Option<DateTime<Utc>> {
+    let d = to_days(date);
+    let lw = RADS * -location.longitude;
+    let lat = RADS * location.latitude;
+
+    transit_jdes(d, lw, lat, SUN_HORIZON).1.map(jde_to_date)
+}
+
+/// The sun's local altitude crosses the standard horizon (-0.833°, setting)
+/// at this instant, or `None` during polar day/night.
+pub fn sunset(date: &DateTime<Utc>, location: &Location) -> Option<DateTime<Utc>> {
+    let d = to_days(date);
+    let lw = RADS * -location.longitude;
+    let lat = RADS * location.latitude;
+
+    transit_jdes(d, lw, lat, SUN_HORIZON).2.map(jde_to_date)
+}
+
+/// The sun's highest point for the day, regardless of whether it clears
+/// the horizon.
+pub fn solar_noon(date: &DateTime<Utc>, location: &Location) -> DateTime<Utc> {
+    let d = to_days(date);
+    let lw = RADS * -location.longitude;
+    let lat = RADS * location.latitude;
+
+    jde_to_date(transit_jdes(d, lw, lat, SUN_HORIZON).0)
+}
+
+/// The daylight/twilight emoji for the sun's local altitude at `date`,
+/// based on `location`. Distinguishes night, dawn, day, and dusk.
+pub fn get_sun_emoji(date: &Option<DateTime<Utc>>, location: &Location) -> &'static str {
+    let date = date.unwrap_or_else(Utc::now);
+    let d = to_days(&date);
+    let alt = sun_altitude(d, location);
+
+    if alt < CIVIL_TWILIGHT {
+        return NIGHT;
+    }
+
+    let later = date.add(Duration::minutes(1));
+    let rising = sun_altitude(to_days(&later), location) >= alt;
+
+    if alt < SUN_HORIZON {
+        return if rising { SUNRISE } else { SUNSET };
+    }
+
+    if rising && alt < RADS * 6.0 {
+        SUNRISE
+    } else if !rising && alt < RADS * 6.0 {
+        SUNSET
+    } else {
+        DAY
+    }
+}
+
+fn main() {
+    println!("{}", get_sun_emoji(&None, &Location { latitude: 0.0, longitude: 0.0, elevation: 0.0 }));
+}
+
+#[test]
+fn a() {
+    let test_date = Utc.ymd(2013, 3, 5).and_hms(0, 0, 0);
+    let test_days = to_days(&test_date);
+
+    let test_sun = sun_coords(test_days);
+    assert!((test_sun.dec - -0.107_490_063_486_385_47).abs() <= std::f64::EPSILON);
+    assert!((test_sun.ra - -0.251_526_492_877_411_9).abs() <= std::f64::EPSILON);
+
+    let location = Location { latitude: 50.5, longitude: 30.5, elevation: 0.0 };
+    assert!(sunrise(&test_date, &location).unwrap() < solar_noon(&test_date, &location));
+    assert!(solar_noon(&test_date, &location) < sunset(&test_date, &location).unwrap());
+
+    // Kyiv (50.5°N, 30.5°E) on 2013-03-05: sunrise ~06:35 local (04:35 UTC),
+    // per almanac data, sanity-checked to within a few minutes.
+    let rise = sunrise(&test_date, &location).unwrap();
+    assert_eq!(rise.date(), test_date.date());
+    assert_eq!(rise.time().hour(), 4);
+    assert!((rise.time().minute() as i64 - 35).abs() <= 3);
+
+    // Near the poles just before the equinox, the sun never clears the horizon.
+    let polar_night = Location { latitude: 85.0, longitude: 0.0, elevation: 0.0 };
+    assert_eq!(sunrise(&test_date, &polar_night), None);
+}